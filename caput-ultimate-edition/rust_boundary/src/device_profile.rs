@@ -0,0 +1,111 @@
+//! device_profile.rs — Per-device flash profiles loaded from a JSON target manifest.
+//!
+//! Lets `genesis-verify firmware` validate against any MCU's flash layout
+//! instead of a single hardcoded limit, by loading a small declarative
+//! manifest of named targets, e.g.:
+//!
+//! ```json
+//! {
+//!   "targets": [
+//!     { "name": "atmega328p", "flash_start": 0, "flash_size": 32768 },
+//!     { "name": "atmega2560", "flash_start": 0, "flash_size": 262144, "algorithm": "sha512" }
+//!   ]
+//! }
+//! ```
+
+use crate::integrity::HashAlgorithm;
+use std::fs;
+use std::path::Path;
+
+/// One target's flash layout and (optional) expected whole-image hash.
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct DeviceProfile {
+    pub name: String,
+    pub flash_start: u32,
+    pub flash_size: u32,
+    #[serde(default)]
+    pub expected_sha256: Option<String>,
+    #[serde(default = "default_algorithm")]
+    pub algorithm: String,
+}
+
+fn default_algorithm() -> String {
+    "sha256".to_string()
+}
+
+impl DeviceProfile {
+    /// The implicit profile used when no `--target`/`--profiles` flags are
+    /// given: the ATmega328P's 32 KB flash at address 0, hashed with SHA-256.
+    pub fn atmega328p_default() -> Self {
+        DeviceProfile {
+            name: "atmega328p".to_string(),
+            flash_start: 0,
+            flash_size: 0x8000,
+            expected_sha256: None,
+            algorithm: default_algorithm(),
+        }
+    }
+
+    /// First address past the end of this target's flash.
+    pub fn flash_end(&self) -> u32 {
+        self.flash_start + self.flash_size
+    }
+
+    pub fn hash_algorithm(&self) -> Result<HashAlgorithm, String> {
+        HashAlgorithm::parse(&self.algorithm)
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct ProfileManifest {
+    targets: Vec<DeviceProfile>,
+}
+
+/// Load a target manifest and return the profile matching `target_name`.
+pub fn load_profile(manifest_path: &Path, target_name: &str) -> Result<DeviceProfile, String> {
+    let content = fs::read_to_string(manifest_path)
+        .map_err(|e| format!("Cannot read profiles {}: {}", manifest_path.display(), e))?;
+    let manifest: ProfileManifest = serde_json::from_str(&content)
+        .map_err(|e| format!("Malformed profile manifest {}: {}", manifest_path.display(), e))?;
+
+    manifest
+        .targets
+        .into_iter()
+        .find(|target| target.name == target_name)
+        .ok_or_else(|| format!("No target '{}' in {}", target_name, manifest_path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_profile_finds_named_target() {
+        let dir = std::env::temp_dir().join("genesis_test_profiles");
+        let _ = fs::create_dir_all(&dir);
+        let manifest_path = dir.join("devices.json");
+        fs::write(
+            &manifest_path,
+            r#"{"targets": [{"name": "atmega2560", "flash_start": 0, "flash_size": 262144, "algorithm": "sha512"}]}"#,
+        )
+        .unwrap();
+
+        let profile = load_profile(&manifest_path, "atmega2560").unwrap();
+        assert_eq!(profile.flash_size, 262144);
+        assert_eq!(profile.hash_algorithm().unwrap(), HashAlgorithm::Sha512);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_load_profile_missing_target() {
+        let dir = std::env::temp_dir().join("genesis_test_profiles_missing");
+        let _ = fs::create_dir_all(&dir);
+        let manifest_path = dir.join("devices.json");
+        fs::write(&manifest_path, r#"{"targets": []}"#).unwrap();
+
+        assert!(load_profile(&manifest_path, "atmega2560").is_err());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
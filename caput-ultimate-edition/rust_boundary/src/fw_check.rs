@@ -0,0 +1,275 @@
+//! fw_check.rs — Intel HEX firmware validation (fail-closed).
+//!
+//! Validates:
+//!   1. Intel HEX record syntax
+//!   2. Per-record 8-bit checksums
+//!   3. Address reconstruction (segment/linear extended addressing) and
+//!      flash boundary, as given by the selected `DeviceProfile`
+//!      (`flash_start`..`flash_end()`) rather than a single hardcoded MCU
+//!   4. Whole-image digest, computed over the reconstructed flash bytes
+//!      rather than the text of the `.hex` file
+
+use crate::device_profile::DeviceProfile;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+// Intel HEX record types.
+const REC_DATA: u8 = 0x00;
+const REC_EOF: u8 = 0x01;
+const REC_EXTENDED_SEGMENT_ADDRESS: u8 = 0x02;
+const REC_START_SEGMENT_ADDRESS: u8 = 0x03;
+const REC_EXTENDED_LINEAR_ADDRESS: u8 = 0x04;
+const REC_START_LINEAR_ADDRESS: u8 = 0x05;
+
+/// A parsed Intel HEX record.
+#[derive(Debug)]
+struct HexRecord {
+    byte_count: u8,
+    address: u16,
+    record_type: u8,
+    data: Vec<u8>,
+    checksum: u8,
+    line_number: usize,
+}
+
+/// Parse a single Intel HEX line. Returns Err on syntax failure.
+fn parse_hex_line(line: &str, line_number: usize) -> Result<HexRecord, String> {
+    let line = line.trim();
+    if !line.starts_with(':') {
+        return Err(format!("Line {}: missing start code ':'", line_number));
+    }
+
+    let hex_str = &line[1..];
+    if hex_str.len() < 10 {
+        return Err(format!("Line {}: too short", line_number));
+    }
+    if hex_str.len() % 2 != 0 || !hex_str.is_ascii() {
+        return Err(format!("Line {}: expected an even number of hex digits", line_number));
+    }
+
+    let bytes: Vec<u8> = (0..hex_str.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex_str[i..i + 2], 16))
+        .collect::<Result<Vec<u8>, _>>()
+        .map_err(|e| format!("Line {}: invalid hex: {}", line_number, e))?;
+
+    let byte_count = bytes[0];
+    let address = ((bytes[1] as u16) << 8) | (bytes[2] as u16);
+    let record_type = bytes[3];
+    let data = bytes[4..bytes.len() - 1].to_vec();
+    let checksum = *bytes.last().unwrap();
+
+    // Verify data length matches byte_count
+    if data.len() != byte_count as usize {
+        return Err(format!(
+            "Line {}: byte count {} but {} data bytes",
+            line_number,
+            byte_count,
+            data.len()
+        ));
+    }
+
+    Ok(HexRecord {
+        byte_count,
+        address,
+        record_type,
+        data,
+        checksum,
+        line_number,
+    })
+}
+
+/// Gate 1: Verify per-record 8-bit two's complement checksum.
+fn verify_checksum(record: &HexRecord) -> Result<(), String> {
+    let mut sum: u8 = 0;
+    sum = sum.wrapping_add(record.byte_count);
+    sum = sum.wrapping_add((record.address >> 8) as u8);
+    sum = sum.wrapping_add((record.address & 0xFF) as u8);
+    sum = sum.wrapping_add(record.record_type);
+    for &b in &record.data {
+        sum = sum.wrapping_add(b);
+    }
+    let expected = (!sum).wrapping_add(1);
+
+    if expected != record.checksum {
+        return Err(format!(
+            "Line {}: checksum mismatch (expected 0x{:02X}, got 0x{:02X})",
+            record.line_number, expected, record.checksum
+        ));
+    }
+    Ok(())
+}
+
+/// Fold one record into the running base offset and sparse flash image.
+/// `base` is updated in place for 0x02/0x04 records; data records are
+/// written into `image` keyed by their absolute (base + record) address,
+/// and an overlapping write is an error.
+fn apply_record(record: &HexRecord, base: &mut u32, image: &mut BTreeMap<u32, u8>) -> Result<(), String> {
+    match record.record_type {
+        REC_DATA => {
+            for (i, &byte) in record.data.iter().enumerate() {
+                let address = *base + record.address as u32 + i as u32;
+                if image.insert(address, byte).is_some() {
+                    return Err(format!(
+                        "Line {}: record overlaps a previously written address 0x{:08X}",
+                        record.line_number, address
+                    ));
+                }
+            }
+        }
+        REC_EOF => {}
+        REC_EXTENDED_SEGMENT_ADDRESS => {
+            let value = extended_address_value(record)?;
+            *base = (value as u32) << 4;
+        }
+        REC_EXTENDED_LINEAR_ADDRESS => {
+            let value = extended_address_value(record)?;
+            *base = (value as u32) << 16;
+        }
+        REC_START_SEGMENT_ADDRESS | REC_START_LINEAR_ADDRESS => {
+            // Start-address records only matter to a loader deciding where
+            // to jump after programming; they don't affect image contents.
+        }
+        other => {
+            return Err(format!(
+                "Line {}: unsupported record type 0x{:02X}",
+                record.line_number, other
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Extract the 16-bit value carried by an 0x02/0x04 address record.
+fn extended_address_value(record: &HexRecord) -> Result<u16, String> {
+    if record.data.len() != 2 {
+        return Err(format!(
+            "Line {}: address record carries {} data bytes, expected 2",
+            record.line_number,
+            record.data.len()
+        ));
+    }
+    Ok(((record.data[0] as u16) << 8) | record.data[1] as u16)
+}
+
+/// Full firmware validation against a device's flash profile. Returns
+/// Ok(hash) or Err on any gate failure.
+pub fn validate_firmware(hex_path: &Path, profile: &DeviceProfile) -> Result<String, String> {
+    let content =
+        fs::read_to_string(hex_path).map_err(|e| format!("Cannot read {}: {}", hex_path.display(), e))?;
+
+    let mut base: u32 = 0;
+    let mut image: BTreeMap<u32, u8> = BTreeMap::new();
+    let mut record_count: usize = 0;
+
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let record = parse_hex_line(line, i + 1)?;
+
+        // Gate 1: checksum
+        verify_checksum(&record)?;
+
+        apply_record(&record, &mut base, &mut image)?;
+
+        record_count += 1;
+    }
+
+    if record_count == 0 {
+        return Err("No records found in hex file".to_string());
+    }
+    if image.is_empty() {
+        return Err("Hex file carries no data records".to_string());
+    }
+
+    // Materialize the contiguous flash region, padding unwritten gaps with
+    // the erased-flash value (0xFF).
+    let min_addr = *image.keys().next().unwrap();
+    let max_addr = *image.keys().next_back().unwrap();
+
+    // Gate 2: bounds-check the reconstructed image against the target's flash range.
+    if min_addr < profile.flash_start || max_addr >= profile.flash_end() {
+        return Err(format!(
+            "Address range 0x{:08X}-0x{:08X} exceeds {} flash range 0x{:08X}-0x{:08X}",
+            min_addr,
+            max_addr,
+            profile.name,
+            profile.flash_start,
+            profile.flash_end()
+        ));
+    }
+
+    let size = (max_addr - min_addr + 1) as usize;
+    let mut flash_image = vec![0xFFu8; size];
+    for (&address, &byte) in &image {
+        flash_image[(address - min_addr) as usize] = byte;
+    }
+
+    // Gate 3: whole-image digest, over the reconstructed bytes.
+    let algo = profile.hash_algorithm()?;
+    let actual_hash = algo.digest_bytes(&flash_image);
+
+    if let Some(expected) = &profile.expected_sha256 {
+        if actual_hash != expected.to_lowercase() {
+            return Err(format!(
+                "{} mismatch: expected {}, got {}",
+                profile.algorithm, expected, actual_hash
+            ));
+        }
+    }
+
+    Ok(actual_hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_valid_eof_record() {
+        let record = parse_hex_line(":00000001FF", 1).unwrap();
+        assert_eq!(record.record_type, 0x01);
+        assert_eq!(record.byte_count, 0);
+    }
+
+    #[test]
+    fn test_checksum_eof_record() {
+        let record = parse_hex_line(":00000001FF", 1).unwrap();
+        assert!(verify_checksum(&record).is_ok());
+    }
+
+    #[test]
+    fn test_bad_checksum() {
+        let record = parse_hex_line(":00000001FE", 1).unwrap();
+        assert!(verify_checksum(&record).is_err());
+    }
+
+    #[test]
+    fn test_extended_linear_address_relocates_data() {
+        // :020000040001F9 -> base = 0x0001_0000
+        let ela = parse_hex_line(":020000040001F9", 1).unwrap();
+        let mut base = 0u32;
+        let mut image = BTreeMap::new();
+        apply_record(&ela, &mut base, &mut image).unwrap();
+        assert_eq!(base, 0x0001_0000);
+
+        // :01000000AA55 -> one data byte 0xAA at offset 0x0000 within the segment
+        let data = parse_hex_line(":01000000AA55", 2).unwrap();
+        apply_record(&data, &mut base, &mut image).unwrap();
+        assert_eq!(image.get(&0x0001_0000), Some(&0xAA));
+    }
+
+    #[test]
+    fn test_overlapping_data_record_is_an_error() {
+        let mut base = 0u32;
+        let mut image = BTreeMap::new();
+        let record = parse_hex_line(":01000000AA55", 1).unwrap();
+        apply_record(&record, &mut base, &mut image).unwrap();
+        let repeat = parse_hex_line(":01000000AA55", 2).unwrap();
+        assert!(apply_record(&repeat, &mut base, &mut image).is_err());
+    }
+}
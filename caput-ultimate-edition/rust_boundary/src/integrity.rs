@@ -3,65 +3,270 @@
 //! This is the Rust integrity boundary. Python may request checks,
 //! but only Rust asserts truth. This module cannot be bypassed.
 
-use sha2::{Digest, Sha256};
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+use std::fmt;
 use std::fs;
 use std::path::Path;
 
+/// A digest algorithm the integrity boundary can verify against.
+///
+/// Every verification path (`verify_file`, `verify_against_hash_file`, the
+/// firmware whole-image gate) dispatches through here, so adding a new
+/// algorithm never means touching the call sites — only this enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    Sha256,
+    Sha512,
+    Sha1,
+    Blake3,
+    Crc32,
+}
+
+impl HashAlgorithm {
+    /// Parse a `--algo` flag value (case-insensitive).
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.to_lowercase().as_str() {
+            "sha256" => Ok(HashAlgorithm::Sha256),
+            "sha512" => Ok(HashAlgorithm::Sha512),
+            "sha1" => Ok(HashAlgorithm::Sha1),
+            "blake3" => Ok(HashAlgorithm::Blake3),
+            "crc32" => Ok(HashAlgorithm::Crc32),
+            other => Err(format!(
+                "Unknown hash algorithm '{}' (expected sha256, sha512, sha1, blake3, crc32)",
+                other
+            )),
+        }
+    }
+
+    /// Canonical lowercase name, used in CLI output and `IntegrityResult`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Sha256 => "sha256",
+            HashAlgorithm::Sha512 => "sha512",
+            HashAlgorithm::Sha1 => "sha1",
+            HashAlgorithm::Blake3 => "blake3",
+            HashAlgorithm::Crc32 => "crc32",
+        }
+    }
+
+    /// Hash raw bytes, returning a lowercase hex digest.
+    pub fn digest_bytes(&self, data: &[u8]) -> String {
+        match self {
+            HashAlgorithm::Sha256 => {
+                let mut hasher = Sha256::new();
+                hasher.update(data);
+                hex::encode(hasher.finalize())
+            }
+            HashAlgorithm::Sha512 => {
+                let mut hasher = Sha512::new();
+                hasher.update(data);
+                hex::encode(hasher.finalize())
+            }
+            HashAlgorithm::Sha1 => {
+                let mut hasher = Sha1::new();
+                hasher.update(data);
+                hex::encode(hasher.finalize())
+            }
+            HashAlgorithm::Blake3 => blake3::hash(data).to_hex().to_string(),
+            HashAlgorithm::Crc32 => format!("{:08x}", crc32fast::hash(data)),
+        }
+    }
+
+    /// Raw digest length in bytes (not the hex-encoded length).
+    pub fn output_len(&self) -> usize {
+        match self {
+            HashAlgorithm::Sha256 | HashAlgorithm::Blake3 => 32,
+            HashAlgorithm::Sha512 => 64,
+            HashAlgorithm::Sha1 => 20,
+            HashAlgorithm::Crc32 => 4,
+        }
+    }
+
+    /// Hash a file. Fails hard on I/O error.
+    pub fn digest_file(&self, path: &Path) -> Result<String, String> {
+        let bytes = fs::read(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        Ok(self.digest_bytes(&bytes))
+    }
+}
+
+impl fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
 /// Result of an integrity check.
 #[derive(Debug, serde::Serialize)]
 pub struct IntegrityResult {
     pub path: String,
+    pub algorithm: String,
     pub expected_hash: String,
     pub actual_hash: String,
     pub valid: bool,
 }
 
 /// Compute SHA-256 of a file. Fails hard on I/O error.
+///
+/// Convenience wrapper over `HashAlgorithm::Sha256` for the common case.
 pub fn sha256_file(path: &Path) -> Result<String, String> {
-    let bytes = fs::read(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
-    let mut hasher = Sha256::new();
-    hasher.update(&bytes);
-    let result = hasher.finalize();
-    Ok(hex::encode(result))
+    HashAlgorithm::Sha256.digest_file(path)
 }
 
 /// Compute SHA-256 of raw bytes.
 pub fn sha256_bytes(data: &[u8]) -> String {
-    let mut hasher = Sha256::new();
-    hasher.update(data);
-    hex::encode(hasher.finalize())
+    HashAlgorithm::Sha256.digest_bytes(data)
 }
 
-/// Verify a file against an expected SHA-256 hash.
+/// Verify a file against an expected hash using the given algorithm.
 /// Returns IntegrityResult; never silently passes.
-pub fn verify_file(path: &Path, expected_hash: &str) -> Result<IntegrityResult, String> {
-    let actual = sha256_file(path)?;
+pub fn verify_file(
+    path: &Path,
+    expected_hash: &str,
+    algo: HashAlgorithm,
+) -> Result<IntegrityResult, String> {
+    let actual = algo.digest_file(path)?;
     let valid = actual == expected_hash.to_lowercase();
 
     Ok(IntegrityResult {
         path: path.display().to_string(),
+        algorithm: algo.as_str().to_string(),
         expected_hash: expected_hash.to_lowercase(),
         actual_hash: actual,
         valid,
     })
 }
 
-/// Verify a file against a .sha256 hash file.
+/// Verify a file against a hash file (e.g. `.sha256`, `.sha512`, `.blake3`).
 /// Hash file format: "<hash>  <filename>" or just "<hash>"
+///
+/// Not yet wired to a CLI command of its own — `manifest::verify_manifest`
+/// is the many-artifact counterpart callers reach for today — but it's kept
+/// as public API for single-sidecar-file verification.
+#[allow(dead_code)]
 pub fn verify_against_hash_file(
     artifact_path: &Path,
     hash_file_path: &Path,
+    algo: HashAlgorithm,
 ) -> Result<IntegrityResult, String> {
     let hash_content = fs::read_to_string(hash_file_path)
         .map_err(|e| format!("Cannot read hash file {}: {}", hash_file_path.display(), e))?;
 
     let expected = hash_content
-        .trim()
         .split_whitespace()
         .next()
         .ok_or_else(|| format!("Empty hash file: {}", hash_file_path.display()))?;
 
-    verify_file(artifact_path, expected)
+    verify_file(artifact_path, expected, algo)
+}
+
+/// One step of a Merkle inclusion proof: the sibling hash needed to
+/// recompute the next level up, and which side it sits on relative to the
+/// node being proved.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ProofStep {
+    pub sibling_hash: String,
+    pub sibling_is_left: bool,
+}
+
+/// Sort paths into the canonical leaf order for a Merkle set: lexicographic
+/// by path, so the root is reproducible regardless of argument order.
+fn canonical_paths<'a>(paths: &[&'a Path]) -> Vec<&'a Path> {
+    let mut sorted = paths.to_vec();
+    sorted.sort_by_key(|p| p.display().to_string());
+    sorted
+}
+
+fn leaf_hashes(paths: &[&Path]) -> Result<Vec<String>, String> {
+    canonical_paths(paths).into_iter().map(sha256_file).collect()
+}
+
+/// `SHA256(left || right)`, operating on the raw digest bytes rather than
+/// their hex text.
+fn combine(left: &str, right: &str) -> String {
+    let mut bytes = hex::decode(left).expect("Merkle node hashes are always valid hex");
+    bytes.extend(hex::decode(right).expect("Merkle node hashes are always valid hex"));
+    sha256_bytes(&bytes)
+}
+
+/// One pairwise-hashing pass up a Merkle level, duplicating the last node
+/// when the level has an odd count.
+fn combine_level(level: &[String]) -> Vec<String> {
+    let mut next = Vec::with_capacity(level.len().div_ceil(2));
+    let mut i = 0;
+    while i < level.len() {
+        let left = &level[i];
+        let right = level.get(i + 1).unwrap_or(left);
+        next.push(combine(left, right));
+        i += 2;
+    }
+    next
+}
+
+/// Build a Merkle root over a set of files' SHA-256 leaf hashes.
+pub fn build_merkle_root(paths: &[&Path]) -> Result<String, String> {
+    if paths.is_empty() {
+        return Err("Cannot build a Merkle root over zero files".to_string());
+    }
+
+    let mut level = leaf_hashes(paths)?;
+    while level.len() > 1 {
+        level = combine_level(&level);
+    }
+
+    Ok(level.remove(0))
+}
+
+/// Produce the inclusion proof for `target` within `paths`: the sibling
+/// hashes (and their left/right position) needed to recompute
+/// `build_merkle_root(paths)` starting from `target`'s own leaf hash.
+pub fn merkle_proof(paths: &[&Path], target: &Path) -> Result<Vec<ProofStep>, String> {
+    let sorted = canonical_paths(paths);
+    let mut index = sorted
+        .iter()
+        .position(|&p| p == target)
+        .ok_or_else(|| format!("{} is not among the files covered by this root", target.display()))?;
+
+    let mut level = leaf_hashes(paths)?;
+    let mut proof = Vec::new();
+
+    while level.len() > 1 {
+        let sibling_index = if index % 2 == 0 { index + 1 } else { index - 1 };
+        let sibling_hash = level
+            .get(sibling_index)
+            .cloned()
+            .unwrap_or_else(|| level[index].clone());
+        proof.push(ProofStep {
+            sibling_hash,
+            sibling_is_left: index % 2 == 1,
+        });
+
+        level = combine_level(&level);
+        index /= 2;
+    }
+
+    Ok(proof)
+}
+
+/// Recompute a Merkle root from a leaf hash and its inclusion proof.
+pub fn merkle_root_from_proof(leaf_hash: &str, proof: &[ProofStep]) -> String {
+    let mut current = leaf_hash.to_string();
+    for step in proof {
+        current = if step.sibling_is_left {
+            combine(&step.sibling_hash, &current)
+        } else {
+            combine(&current, &step.sibling_hash)
+        };
+    }
+    current
+}
+
+/// Verify a single file against a published Merkle root using its
+/// inclusion proof, without needing any other file in the set.
+pub fn merkle_verify_file(path: &Path, expected_root: &str, proof: &[ProofStep]) -> Result<bool, String> {
+    let leaf_hash = sha256_file(path)?;
+    let computed_root = merkle_root_from_proof(&leaf_hash, proof);
+    Ok(computed_root.to_lowercase() == expected_root.to_lowercase())
 }
 
 #[cfg(test)]
@@ -79,6 +284,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_blake3_bytes_known_value() {
+        // BLAKE3 of empty string
+        let hash = HashAlgorithm::Blake3.digest_bytes(b"");
+        assert_eq!(
+            hash,
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+        );
+    }
+
+    #[test]
+    fn test_parse_algo_case_insensitive() {
+        assert_eq!(HashAlgorithm::parse("SHA256").unwrap(), HashAlgorithm::Sha256);
+        assert!(HashAlgorithm::parse("rot13").is_err());
+    }
+
     #[test]
     fn test_verify_file_pass() {
         let dir = std::env::temp_dir().join("genesis_test_pass");
@@ -88,8 +309,9 @@ mod tests {
         f.write_all(b"hello genesis").unwrap();
 
         let expected = sha256_bytes(b"hello genesis");
-        let result = verify_file(&file, &expected).unwrap();
+        let result = verify_file(&file, &expected, HashAlgorithm::Sha256).unwrap();
         assert!(result.valid);
+        assert_eq!(result.algorithm, "sha256");
 
         let _ = fs::remove_dir_all(&dir);
     }
@@ -102,9 +324,72 @@ mod tests {
         let mut f = fs::File::create(&file).unwrap();
         f.write_all(b"hello genesis").unwrap();
 
-        let result = verify_file(&file, "0000000000000000000000000000000000000000000000000000000000000000").unwrap();
+        let result = verify_file(
+            &file,
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            HashAlgorithm::Sha256,
+        )
+        .unwrap();
         assert!(!result.valid);
 
         let _ = fs::remove_dir_all(&dir);
     }
+
+    fn write_temp_files(dir_name: &str, contents: &[(&str, &[u8])]) -> (std::path::PathBuf, Vec<std::path::PathBuf>) {
+        let dir = std::env::temp_dir().join(dir_name);
+        let _ = fs::create_dir_all(&dir);
+        let mut paths = Vec::new();
+        for (name, data) in contents {
+            let path = dir.join(name);
+            fs::write(&path, data).unwrap();
+            paths.push(path);
+        }
+        (dir, paths)
+    }
+
+    #[test]
+    fn test_merkle_root_is_order_independent() {
+        let (dir, paths) = write_temp_files(
+            "genesis_test_merkle_order",
+            &[("a.bin", b"alpha"), ("b.bin", b"bravo"), ("c.bin", b"charlie")],
+        );
+        let refs: Vec<&Path> = paths.iter().map(|p| p.as_path()).collect();
+        let mut reversed = refs.clone();
+        reversed.reverse();
+
+        assert_eq!(
+            build_merkle_root(&refs).unwrap(),
+            build_merkle_root(&reversed).unwrap()
+        );
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_merkle_proof_round_trips_to_root() {
+        let (dir, paths) = write_temp_files(
+            "genesis_test_merkle_proof",
+            &[("a.bin", b"alpha"), ("b.bin", b"bravo"), ("c.bin", b"charlie")],
+        );
+        let refs: Vec<&Path> = paths.iter().map(|p| p.as_path()).collect();
+        let root = build_merkle_root(&refs).unwrap();
+
+        for path in &paths {
+            let proof = merkle_proof(&refs, path).unwrap();
+            assert!(merkle_verify_file(path, &root, &proof).unwrap());
+        }
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_merkle_verify_rejects_wrong_root() {
+        let (dir, paths) = write_temp_files("genesis_test_merkle_tamper", &[("a.bin", b"alpha"), ("b.bin", b"bravo")]);
+        let refs: Vec<&Path> = paths.iter().map(|p| p.as_path()).collect();
+        let proof = merkle_proof(&refs, &paths[0]).unwrap();
+
+        assert!(!merkle_verify_file(&paths[0], &"0".repeat(64), &proof).unwrap());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
 }
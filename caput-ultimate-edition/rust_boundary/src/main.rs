@@ -1,13 +1,30 @@
 //! genesis-verify — CLI entry point for the Rust integrity boundary.
 //!
 //! Usage:
-//!   genesis-verify hash <file>
-//!   genesis-verify check <file> <expected_sha256>
-//!   genesis-verify firmware <hex_file> [expected_sha256]
+//!   genesis-verify hash [--algo <algo>] <file>
+//!   genesis-verify check [--algo <algo>] <file> <expected_hash>
+//!   genesis-verify check-list <manifest>
+//!   genesis-verify firmware [--target <name> --profiles <manifest>] <hex> [expected_sha256]
+//!   genesis-verify pcr4 [--algo <algo>] --images <dir> <eventlog> [expected_pcr4]
+//!   genesis-verify merkle-root [--proof-for <file>] <files...>
+//!   genesis-verify merkle-verify <file> <root> <proof.json>
+//!
+//! Supported `--algo` values: sha256 (default), sha512, sha1, blake3, crc32
+//! (`pcr4` only supports the TPM banks: sha256, sha512, sha1).
+//! `--target`/`--profiles` select a device's flash layout from a JSON
+//! manifest (see `device_profile.rs`); omitted, firmware validation falls
+//! back to the ATmega328P flash layout (0x0000-0x8000, SHA-256 digest).
 
+mod device_profile;
 mod fw_check;
 mod integrity;
+mod manifest;
+mod pcr4;
+mod pe_authenticode;
 
+use device_profile::DeviceProfile;
+use integrity::HashAlgorithm;
+use std::fs;
 use std::path::Path;
 use std::process;
 
@@ -15,12 +32,52 @@ fn print_usage() {
     eprintln!("genesis-verify — GENESIS Rust Integrity Boundary");
     eprintln!();
     eprintln!("Usage:");
-    eprintln!("  genesis-verify hash <file>                      Compute SHA-256");
-    eprintln!("  genesis-verify check <file> <expected_sha256>   Verify file hash");
-    eprintln!("  genesis-verify firmware <hex> [expected_sha256]  Validate Intel HEX firmware");
+    eprintln!("  genesis-verify hash [--algo <algo>] <file>              Compute a digest");
+    eprintln!("  genesis-verify check [--algo <algo>] <file> <expected>  Verify file digest");
+    eprintln!("  genesis-verify check-list <manifest>                    Verify a checksum manifest");
+    eprintln!("  genesis-verify firmware [--target <name> --profiles <manifest>] <hex> [expected]");
+    eprintln!("                                                           Validate Intel HEX firmware");
+    eprintln!("  genesis-verify pcr4 [--algo <algo>] --images <dir> <eventlog> [expected_pcr4]");
+    eprintln!("                                                           Replay a TCG2 event log into PCR4");
+    eprintln!("  genesis-verify merkle-root [--proof-for <file>] <files...>  Build a Merkle root");
+    eprintln!("  genesis-verify merkle-verify <file> <root> <proof.json>     Verify via inclusion proof");
+    eprintln!();
+    eprintln!("  <algo> is one of: sha256 (default), sha512, sha1, blake3, crc32");
     process::exit(1);
 }
 
+/// Pull `<flag> <value>` out of the positional args, returning the value (if
+/// present) and the remaining args with that pair removed.
+fn take_flag(args: &[String], flag: &str) -> Result<(Option<String>, Vec<String>), String> {
+    let mut value = None;
+    let mut rest = Vec::with_capacity(args.len());
+    let mut iter = args.iter().cloned();
+
+    while let Some(arg) = iter.next() {
+        if arg == flag {
+            value = Some(
+                iter.next()
+                    .ok_or_else(|| format!("Missing value for {}", flag))?,
+            );
+        } else {
+            rest.push(arg);
+        }
+    }
+
+    Ok((value, rest))
+}
+
+/// Pull an optional `--algo <name>` flag out of the positional args,
+/// returning the parsed algorithm (default SHA-256) and the remaining args.
+fn take_algo_flag(args: &[String]) -> Result<(HashAlgorithm, Vec<String>), String> {
+    let (value, rest) = take_flag(args, "--algo")?;
+    let algo = match value {
+        Some(name) => HashAlgorithm::parse(&name)?,
+        None => HashAlgorithm::Sha256,
+    };
+    Ok((algo, rest))
+}
+
 fn main() {
     let args: Vec<String> = std::env::args().collect();
 
@@ -28,14 +85,25 @@ fn main() {
         print_usage();
     }
 
-    let command = &args[1];
-    let path = Path::new(&args[2]);
+    let command = args[1].clone();
+    let (algo, rest) = match take_algo_flag(&args[2..]) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("FAIL: {}", e);
+            process::exit(1);
+        }
+    };
+
+    if rest.is_empty() {
+        print_usage();
+    }
 
     match command.as_str() {
         "hash" => {
-            match integrity::sha256_file(path) {
+            let path = Path::new(&rest[0]);
+            match algo.digest_file(path) {
                 Ok(hash) => {
-                    println!("{}  {}", hash, path.display());
+                    println!("{}  {}  ({})", hash, path.display(), algo);
                 }
                 Err(e) => {
                     eprintln!("FAIL: {}", e);
@@ -45,12 +113,13 @@ fn main() {
         }
 
         "check" => {
-            if args.len() < 4 {
+            let path = Path::new(&rest[0]);
+            if rest.len() < 2 {
                 eprintln!("Missing expected hash argument");
                 process::exit(1);
             }
-            let expected = &args[3];
-            match integrity::verify_file(path, expected) {
+            let expected = &rest[1];
+            match integrity::verify_file(path, expected, algo) {
                 Ok(result) => {
                     let json = serde_json::to_string_pretty(&result).unwrap();
                     println!("{}", json);
@@ -65,11 +134,195 @@ fn main() {
             }
         }
 
+        "check-list" => {
+            let manifest_path = Path::new(&rest[0]);
+            match manifest::verify_manifest(manifest_path) {
+                Ok((results, summary)) => {
+                    for (path, status) in &results {
+                        match status {
+                            manifest::EntryStatus::Ok => println!("{}: OK", path),
+                            manifest::EntryStatus::Failed { actual_hash } => {
+                                println!("{}: FAILED (got {})", path, actual_hash)
+                            }
+                            manifest::EntryStatus::Unreadable(e) => {
+                                println!("{}: FAILED open or read ({})", path, e)
+                            }
+                        }
+                    }
+                    println!(
+                        "{} files verified, {} failed, {} unreadable",
+                        summary.verified, summary.failed, summary.unreadable
+                    );
+                    if summary.failed > 0 || summary.unreadable > 0 {
+                        process::exit(1);
+                    }
+                }
+                Err(e) => {
+                    eprintln!("FAIL: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+
         "firmware" => {
-            let expected = args.get(3).map(|s| s.as_str());
-            match fw_check::validate_firmware(path, expected) {
+            let (target, rest) = match take_flag(&rest, "--target") {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    eprintln!("FAIL: {}", e);
+                    process::exit(1);
+                }
+            };
+            let (profiles, rest) = match take_flag(&rest, "--profiles") {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    eprintln!("FAIL: {}", e);
+                    process::exit(1);
+                }
+            };
+
+            let mut profile = match (target, profiles) {
+                (Some(target), Some(profiles)) => {
+                    match device_profile::load_profile(Path::new(&profiles), &target) {
+                        Ok(profile) => profile,
+                        Err(e) => {
+                            eprintln!("FAIL: {}", e);
+                            process::exit(1);
+                        }
+                    }
+                }
+                (None, None) => DeviceProfile::atmega328p_default(),
+                _ => {
+                    eprintln!("FAIL: --target and --profiles must be given together");
+                    process::exit(1);
+                }
+            };
+
+            if rest.is_empty() {
+                print_usage();
+            }
+            let path = Path::new(&rest[0]);
+            if let Some(expected) = rest.get(1) {
+                profile.expected_sha256 = Some(expected.clone());
+            }
+
+            match fw_check::validate_firmware(path, &profile) {
                 Ok(hash) => {
-                    println!("PASS: {} (sha256: {})", path.display(), hash);
+                    println!(
+                        "PASS: {} (target: {}, {}: {})",
+                        path.display(),
+                        profile.name,
+                        profile.algorithm,
+                        hash
+                    );
+                }
+                Err(e) => {
+                    eprintln!("FAIL: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+
+        "pcr4" => {
+            let (images_dir, rest) = match take_flag(&rest, "--images") {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    eprintln!("FAIL: {}", e);
+                    process::exit(1);
+                }
+            };
+            let images_dir = match images_dir {
+                Some(dir) => dir,
+                None => {
+                    eprintln!("FAIL: --images <dir> is required");
+                    process::exit(1);
+                }
+            };
+
+            if rest.is_empty() {
+                print_usage();
+            }
+            let eventlog_path = Path::new(&rest[0]);
+            let expected = rest.get(1);
+
+            match pcr4::replay_pcr4(eventlog_path, Path::new(&images_dir), algo) {
+                Ok(pcr) => {
+                    println!("PCR4: {}", pcr);
+                    if let Some(expected) = expected {
+                        if pcr != expected.to_lowercase() {
+                            eprintln!("FAIL: PCR4 mismatch: expected {}, got {}", expected, pcr);
+                            process::exit(1);
+                        }
+                    }
+                }
+                Err(e) => {
+                    eprintln!("FAIL: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+
+        "merkle-root" => {
+            let (proof_for, rest) = match take_flag(&rest, "--proof-for") {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    eprintln!("FAIL: {}", e);
+                    process::exit(1);
+                }
+            };
+            if rest.is_empty() {
+                print_usage();
+            }
+            let paths: Vec<&Path> = rest.iter().map(|p| Path::new(p.as_str())).collect();
+
+            match integrity::build_merkle_root(&paths) {
+                Ok(root) => {
+                    println!("{}", root);
+                    if let Some(target) = proof_for {
+                        match integrity::merkle_proof(&paths, Path::new(&target)) {
+                            Ok(proof) => println!("{}", serde_json::to_string_pretty(&proof).unwrap()),
+                            Err(e) => {
+                                eprintln!("FAIL: {}", e);
+                                process::exit(1);
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    eprintln!("FAIL: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+
+        "merkle-verify" => {
+            if rest.len() < 3 {
+                eprintln!("Usage: genesis-verify merkle-verify <file> <root> <proof.json>");
+                process::exit(1);
+            }
+            let path = Path::new(&rest[0]);
+            let root = &rest[1];
+            let proof_path = Path::new(&rest[2]);
+
+            let proof_json = match fs::read_to_string(proof_path) {
+                Ok(contents) => contents,
+                Err(e) => {
+                    eprintln!("FAIL: Cannot read proof {}: {}", proof_path.display(), e);
+                    process::exit(1);
+                }
+            };
+            let proof: Vec<integrity::ProofStep> = match serde_json::from_str(&proof_json) {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    eprintln!("FAIL: Malformed proof {}: {}", proof_path.display(), e);
+                    process::exit(1);
+                }
+            };
+
+            match integrity::merkle_verify_file(path, root, &proof) {
+                Ok(true) => println!("{}: OK", path.display()),
+                Ok(false) => {
+                    println!("{}: FAILED", path.display());
+                    process::exit(1);
                 }
                 Err(e) => {
                     eprintln!("FAIL: {}", e);
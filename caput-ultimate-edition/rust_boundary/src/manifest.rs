@@ -0,0 +1,218 @@
+//! manifest.rs — Batch verification against a checksum manifest file.
+//!
+//! Reads a coreutils-style checksum file (`<hash>  <path>` per line, or the
+//! BSD-tagged `ALGO (path) = hash` form), hashes every referenced file, and
+//! reports pass/fail per entry plus an aggregate summary — the many-artifact
+//! counterpart to `integrity::verify_against_hash_file`.
+
+use crate::integrity::{self, HashAlgorithm};
+use std::fs;
+use std::path::Path;
+
+/// One parsed line of a checksum manifest.
+#[derive(Debug)]
+pub struct ManifestEntry {
+    pub path: String,
+    pub algorithm: HashAlgorithm,
+    pub expected_hash: String,
+}
+
+/// Outcome of checking a single manifest entry.
+#[derive(Debug)]
+pub enum EntryStatus {
+    Ok,
+    Failed { actual_hash: String },
+    Unreadable(String),
+}
+
+/// Aggregate counts across a manifest run.
+#[derive(Debug, Default)]
+pub struct ManifestSummary {
+    pub verified: usize,
+    pub failed: usize,
+    pub unreadable: usize,
+}
+
+/// Parse one manifest line. Blank lines and `#` comments yield `None`.
+pub fn parse_manifest_line(line: &str) -> Result<Option<ManifestEntry>, String> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+
+    if let Some(entry) = parse_tagged_line(line) {
+        return Ok(Some(entry));
+    }
+    parse_plain_line(line).map(Some)
+}
+
+/// BSD-style tagged line: `SHA256 (path/to/file) = <hash>`
+fn parse_tagged_line(line: &str) -> Option<ManifestEntry> {
+    let open = line.find(" (")?;
+    let algorithm = HashAlgorithm::parse(&line[..open]).ok()?;
+    let rest = &line[open + 2..];
+    let close = rest.find(") = ")?;
+
+    Some(ManifestEntry {
+        path: rest[..close].to_string(),
+        algorithm,
+        expected_hash: rest[close + 4..].trim().to_lowercase(),
+    })
+}
+
+/// coreutils-style plain line: `<hash>  <path>`. The algorithm is inferred
+/// from the hash's hex length; a 64-char hash is assumed to be SHA-256,
+/// since BLAKE3 shares that length and the plain format has no other
+/// distinguishing marker — use the tagged form above to disambiguate.
+fn parse_plain_line(line: &str) -> Result<ManifestEntry, String> {
+    let mut parts = line.splitn(2, char::is_whitespace);
+    let hash = parts
+        .next()
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| format!("Malformed manifest line: {}", line))?;
+    let path = parts
+        .next()
+        .ok_or_else(|| format!("Missing path in manifest line: {}", line))?
+        .trim_start();
+    let path = path.strip_prefix('*').unwrap_or(path); // coreutils binary-mode marker
+
+    let algorithm = infer_algo_from_hash_len(hash.len())
+        .ok_or_else(|| format!("Cannot infer hash algorithm from '{}' in line: {}", hash, line))?;
+
+    Ok(ManifestEntry {
+        path: path.to_string(),
+        algorithm,
+        expected_hash: hash.to_lowercase(),
+    })
+}
+
+fn infer_algo_from_hash_len(len: usize) -> Option<HashAlgorithm> {
+    match len {
+        8 => Some(HashAlgorithm::Crc32),
+        40 => Some(HashAlgorithm::Sha1),
+        64 => Some(HashAlgorithm::Sha256),
+        128 => Some(HashAlgorithm::Sha512),
+        _ => None,
+    }
+}
+
+/// Verify every entry in a manifest, resolving relative paths against the
+/// manifest's own directory. Returns the per-entry outcomes in file order
+/// plus the aggregate summary.
+pub fn verify_manifest(
+    manifest_path: &Path,
+) -> Result<(Vec<(String, EntryStatus)>, ManifestSummary), String> {
+    let content = fs::read_to_string(manifest_path)
+        .map_err(|e| format!("Cannot read manifest {}: {}", manifest_path.display(), e))?;
+    let base_dir = manifest_path.parent().filter(|p| !p.as_os_str().is_empty());
+
+    let mut results = Vec::new();
+    let mut summary = ManifestSummary::default();
+
+    for (i, line) in content.lines().enumerate() {
+        let entry = match parse_manifest_line(line) {
+            Ok(Some(entry)) => entry,
+            Ok(None) => continue,
+            Err(e) => {
+                // A single malformed line (truncated hash, stray edit) is a
+                // per-entry failure, not a reason to discard every result
+                // already accumulated for the rest of the manifest.
+                summary.unreadable += 1;
+                results.push((line.trim().to_string(), EntryStatus::Unreadable(format!("{} (line {})", e, i + 1))));
+                continue;
+            }
+        };
+
+        let artifact_path = match base_dir {
+            Some(dir) => dir.join(&entry.path),
+            None => Path::new(&entry.path).to_path_buf(),
+        };
+
+        match integrity::verify_file(&artifact_path, &entry.expected_hash, entry.algorithm) {
+            Ok(result) if result.valid => {
+                summary.verified += 1;
+                results.push((entry.path, EntryStatus::Ok));
+            }
+            Ok(result) => {
+                summary.failed += 1;
+                results.push((
+                    entry.path,
+                    EntryStatus::Failed {
+                        actual_hash: result.actual_hash,
+                    },
+                ));
+            }
+            Err(e) => {
+                summary.unreadable += 1;
+                results.push((entry.path, EntryStatus::Unreadable(e)));
+            }
+        }
+    }
+
+    Ok((results, summary))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_plain_line_infers_sha256() {
+        let entry = parse_manifest_line(
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855  empty.bin",
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(entry.algorithm, HashAlgorithm::Sha256);
+        assert_eq!(entry.path, "empty.bin");
+    }
+
+    #[test]
+    fn test_parse_tagged_line() {
+        let entry = parse_manifest_line("SHA512 (build/firmware.hex) = abcd1234")
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.algorithm, HashAlgorithm::Sha512);
+        assert_eq!(entry.path, "build/firmware.hex");
+        assert_eq!(entry.expected_hash, "abcd1234");
+    }
+
+    #[test]
+    fn test_blank_and_comment_lines_are_skipped() {
+        assert!(parse_manifest_line("").unwrap().is_none());
+        assert!(parse_manifest_line("# generated by release.sh").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_verify_manifest_end_to_end() {
+        let dir = std::env::temp_dir().join("genesis_test_manifest_e2e");
+        let _ = fs::create_dir_all(&dir);
+
+        fs::write(dir.join("good.bin"), b"hello genesis").unwrap();
+        fs::write(dir.join("bad.bin"), b"tampered").unwrap();
+
+        let good_hash = integrity::sha256_bytes(b"hello genesis");
+        let manifest_path = dir.join("SHA256SUMS");
+        fs::write(
+            &manifest_path,
+            format!(
+                "{}  good.bin\n{}  bad.bin\nnot-a-valid-manifest-line\n{}  missing.bin\n",
+                good_hash, good_hash, good_hash
+            ),
+        )
+        .unwrap();
+
+        let (results, summary) = verify_manifest(&manifest_path).unwrap();
+
+        assert_eq!(summary.verified, 1);
+        assert_eq!(summary.failed, 1);
+        assert_eq!(summary.unreadable, 2); // malformed line + missing file
+        assert_eq!(results.len(), 4);
+        assert!(matches!(results[0].1, EntryStatus::Ok));
+        assert!(matches!(results[1].1, EntryStatus::Failed { .. }));
+        assert!(matches!(results[2].1, EntryStatus::Unreadable(_)));
+        assert!(matches!(results[3].1, EntryStatus::Unreadable(_)));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,299 @@
+//! pcr4.rs — TPM PCR4 replay from a TCG2 UEFI event log.
+//!
+//! Replays the `EV_EFI_BOOT_SERVICES_APPLICATION` measurements recorded in a
+//! crypto-agile (`TCG_PCR_EVENT2`) event log into PCR4, recomputing each
+//! entry's Authenticode digest from the on-disk image so a tampered binary
+//! — or a tampered log — is caught by a mismatch rather than trusted at
+//! face value.
+//!
+//! A crypto-agile TCG2 event log opens with exactly one legacy-format
+//! `TCG_PCR_EVENT` record (the "Spec ID Event", fixed 20-byte SHA-1 digest,
+//! no `DigestCount`) before any `TCG_PCR_EVENT2` records follow, so that
+//! header record is parsed on its own before the crypto-agile loop takes
+//! over. Every later record is parsed as `TCG_PCR_EVENT2`; logs made only of
+//! legacy-format records are not supported.
+//!
+//! Image correlation: the event log identifies a loaded image by UEFI
+//! device path, not by a filename resolvable on an arbitrary filesystem, so
+//! `--images <dir>` is expected to hold one file per boot-application
+//! event, named by that event's position in log order: `0.efi`, `1.efi`, …
+
+use crate::integrity::HashAlgorithm;
+use crate::pe_authenticode;
+use std::fs;
+use std::path::Path;
+
+const EV_NO_ACTION: u32 = 0x0000_0003;
+const EV_EFI_BOOT_SERVICES_APPLICATION: u32 = 0x8000_0002;
+
+const TPM_ALG_SHA1: u16 = 0x0004;
+const TPM_ALG_SHA256: u16 = 0x000B;
+const TPM_ALG_SHA512: u16 = 0x000D;
+
+/// Digest length of the legacy `TCG_PCR_EVENT` header record, which always
+/// carries a fixed-size SHA-1 digest regardless of the log's later banks.
+const LEGACY_HEADER_DIGEST_LEN: usize = 20;
+
+fn to_hash_algorithm(algorithm_id: u16) -> Option<HashAlgorithm> {
+    match algorithm_id {
+        TPM_ALG_SHA1 => Some(HashAlgorithm::Sha1),
+        TPM_ALG_SHA256 => Some(HashAlgorithm::Sha256),
+        TPM_ALG_SHA512 => Some(HashAlgorithm::Sha512),
+        _ => None,
+    }
+}
+
+/// One parsed `TCG_PCR_EVENT2` record: PCR index, event type, and every
+/// digest bank the log carries for it.
+struct LogEvent {
+    pcr_index: u32,
+    event_type: u32,
+    digests: Vec<(u16, Vec<u8>)>,
+}
+
+/// Parse the log's one leading legacy-format header record: `PCRIndex`,
+/// `EventType`, a fixed 20-byte SHA-1 digest, `EventSize`, then `Event`.
+/// Returns the parsed event and the offset of the next (crypto-agile) record.
+fn parse_legacy_header_event(data: &[u8], offset: usize) -> Result<(LogEvent, usize), String> {
+    let pcr_index = read_u32(data, offset, "PCRIndex")?;
+    let event_type = read_u32(data, offset + 4, "EventType")?;
+
+    let digest_offset = offset + 8;
+    let digest = data
+        .get(digest_offset..digest_offset + LEGACY_HEADER_DIGEST_LEN)
+        .ok_or_else(|| format!("Truncated event log: missing header digest at offset {}", digest_offset))?
+        .to_vec();
+
+    let event_size_offset = digest_offset + LEGACY_HEADER_DIGEST_LEN;
+    let event_size = read_u32(data, event_size_offset, "EventSize")? as usize;
+    let event_offset = event_size_offset + 4;
+    if event_offset + event_size > data.len() {
+        return Err(format!("Truncated event log: missing header event data at offset {}", event_offset));
+    }
+
+    Ok((
+        LogEvent {
+            pcr_index,
+            event_type,
+            digests: vec![(TPM_ALG_SHA1, digest)],
+        },
+        event_offset + event_size,
+    ))
+}
+
+/// Parse every event out of a TCG2 event log: one leading legacy-format
+/// header record, followed by crypto-agile (`TCG_PCR_EVENT2`) records.
+fn parse_event_log(data: &[u8]) -> Result<Vec<LogEvent>, String> {
+    let mut events = Vec::new();
+    let mut offset = 0usize;
+
+    if offset < data.len() {
+        let (header_event, next_offset) = parse_legacy_header_event(data, offset)?;
+        events.push(header_event);
+        offset = next_offset;
+    }
+
+    while offset < data.len() {
+        let pcr_index = read_u32(data, offset, "PCRIndex")?;
+        let event_type = read_u32(data, offset + 4, "EventType")?;
+        let digest_count = read_u32(data, offset + 8, "DigestCount")? as usize;
+        let mut cursor = offset + 12;
+
+        let mut digests = Vec::with_capacity(digest_count);
+        for _ in 0..digest_count {
+            let algorithm_id = read_u16(data, cursor, "AlgorithmId")?;
+            let len = to_hash_algorithm(algorithm_id)
+                .map(|algo| algo.output_len())
+                .ok_or_else(|| format!("Unsupported TPM_ALG_ID 0x{:04X} at offset {}", algorithm_id, cursor))?;
+            cursor += 2;
+            if cursor + len > data.len() {
+                return Err(format!("Truncated digest at offset {}", cursor));
+            }
+            digests.push((algorithm_id, data[cursor..cursor + len].to_vec()));
+            cursor += len;
+        }
+
+        let event_size = read_u32(data, cursor, "EventSize")? as usize;
+        cursor += 4;
+        if cursor + event_size > data.len() {
+            return Err(format!("Truncated event data at offset {}", cursor));
+        }
+        cursor += event_size;
+
+        events.push(LogEvent {
+            pcr_index,
+            event_type,
+            digests,
+        });
+        offset = cursor;
+    }
+
+    Ok(events)
+}
+
+fn read_u32(data: &[u8], offset: usize, field: &str) -> Result<u32, String> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+        .ok_or_else(|| format!("Truncated event log: missing {} at offset {}", field, offset))
+}
+
+fn read_u16(data: &[u8], offset: usize, field: &str) -> Result<u16, String> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes(b.try_into().unwrap()))
+        .ok_or_else(|| format!("Truncated event log: missing {} at offset {}", field, offset))
+}
+
+/// `PCR = H(PCR || measured_digest)`
+fn extend(bank: HashAlgorithm, pcr: &[u8], measured_digest: &[u8]) -> Vec<u8> {
+    let mut concatenated = Vec::with_capacity(pcr.len() + measured_digest.len());
+    concatenated.extend_from_slice(pcr);
+    concatenated.extend_from_slice(measured_digest);
+    hex::decode(bank.digest_bytes(&concatenated)).expect("digest_bytes always returns valid hex")
+}
+
+/// Replay PCR4 from `eventlog_path`, recomputing boot-application digests
+/// from files in `images_dir`, using the single hash bank `bank`. Returns
+/// the final PCR4 value as lowercase hex. An empty or all-non-PCR4 log
+/// yields the all-zero starting register, not an error.
+pub fn replay_pcr4(eventlog_path: &Path, images_dir: &Path, bank: HashAlgorithm) -> Result<String, String> {
+    let log_bytes = fs::read(eventlog_path)
+        .map_err(|e| format!("Cannot read event log {}: {}", eventlog_path.display(), e))?;
+    let events = parse_event_log(&log_bytes)?;
+
+    let mut pcr = vec![0u8; bank.output_len()];
+    let mut boot_app_index = 0usize;
+
+    for event in &events {
+        if event.event_type == EV_NO_ACTION || event.pcr_index != 4 {
+            continue;
+        }
+
+        let logged_digest = event
+            .digests
+            .iter()
+            .find(|(algorithm_id, _)| to_hash_algorithm(*algorithm_id) == Some(bank))
+            .map(|(_, digest)| digest.clone())
+            .ok_or_else(|| format!("PCR4 event has no digest for requested bank {}", bank))?;
+
+        if event.event_type == EV_EFI_BOOT_SERVICES_APPLICATION {
+            let image_path = images_dir.join(format!("{}.efi", boot_app_index));
+            let recomputed_hex = pe_authenticode::authenticode_digest_file(&image_path, bank)?;
+            let recomputed = hex::decode(&recomputed_hex)
+                .map_err(|e| format!("Internal error decoding recomputed digest: {}", e))?;
+
+            if recomputed != logged_digest {
+                return Err(format!(
+                    "PCR4 measurement mismatch for {}: log says {}, on-disk image hashes to {}",
+                    image_path.display(),
+                    hex::encode(&logged_digest),
+                    recomputed_hex
+                ));
+            }
+            boot_app_index += 1;
+        }
+
+        pcr = extend(bank, &pcr, &logged_digest);
+    }
+
+    Ok(hex::encode(pcr))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn legacy_header_event(pcr_index: u32, event_type: u32, event: &[u8]) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&pcr_index.to_le_bytes());
+        buf.extend_from_slice(&event_type.to_le_bytes());
+        buf.extend_from_slice(&[0u8; LEGACY_HEADER_DIGEST_LEN]);
+        buf.extend_from_slice(&(event.len() as u32).to_le_bytes());
+        buf.extend_from_slice(event);
+        buf
+    }
+
+    fn pcr_event2(pcr_index: u32, event_type: u32, digest: &[u8], event: &[u8]) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&pcr_index.to_le_bytes());
+        buf.extend_from_slice(&event_type.to_le_bytes());
+        buf.extend_from_slice(&1u32.to_le_bytes()); // DigestCount
+        buf.extend_from_slice(&TPM_ALG_SHA256.to_le_bytes());
+        buf.extend_from_slice(digest);
+        buf.extend_from_slice(&(event.len() as u32).to_le_bytes());
+        buf.extend_from_slice(event);
+        buf
+    }
+
+    /// A minimal valid (unsigned, no Certificate Table) PE32 image, just
+    /// large enough for `pe_authenticode::PeLayout::parse` to succeed.
+    fn minimal_pe32() -> Vec<u8> {
+        const IMAGE_DIRECTORY_ENTRY_SECURITY: usize = 4;
+        let optional_header_offset = 0x40 + 4 + 20;
+        let data_directory_offset = optional_header_offset + 96;
+        let cert_entry_offset = data_directory_offset + IMAGE_DIRECTORY_ENTRY_SECURITY * 8;
+        let mut image = vec![0u8; cert_entry_offset + 8];
+
+        image[0x3C..0x40].copy_from_slice(&(0x40u32).to_le_bytes());
+        image[0x40..0x44].copy_from_slice(b"PE\0\0");
+        image[0x40 + 4 + 16..0x40 + 4 + 18].copy_from_slice(&96u16.to_le_bytes());
+        image[optional_header_offset..optional_header_offset + 2].copy_from_slice(&0x10bu16.to_le_bytes());
+        image.extend_from_slice(b"image-body");
+        image
+    }
+
+    #[test]
+    fn test_empty_log_yields_all_zero_pcr() {
+        let dir = std::env::temp_dir().join("genesis_test_pcr4_empty");
+        let _ = fs::create_dir_all(&dir);
+        let log_path = dir.join("eventlog.bin");
+        fs::write(&log_path, []).unwrap();
+
+        let pcr = replay_pcr4(&log_path, &dir, HashAlgorithm::Sha256).unwrap();
+        assert_eq!(pcr, "0".repeat(64));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_non_pcr4_event_is_ignored() {
+        let dir = std::env::temp_dir().join("genesis_test_pcr4_skip");
+        let _ = fs::create_dir_all(&dir);
+        let log_path = dir.join("eventlog.bin");
+
+        let mut log = legacy_header_event(0, EV_NO_ACTION, b"spec-id-event");
+        log.extend(pcr_event2(1, EV_EFI_BOOT_SERVICES_APPLICATION, &[0u8; 32], b"unrelated-pcr1-event"));
+        fs::write(&log_path, &log).unwrap();
+
+        let pcr = replay_pcr4(&log_path, &dir, HashAlgorithm::Sha256).unwrap();
+        assert_eq!(pcr, "0".repeat(64));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_realistic_log_replays_boot_app_into_pcr4() {
+        let dir = std::env::temp_dir().join("genesis_test_pcr4_realistic");
+        let _ = fs::create_dir_all(&dir);
+
+        let image = minimal_pe32();
+        fs::write(dir.join("0.efi"), &image).unwrap();
+        let measured_digest_hex = pe_authenticode::authenticode_digest_bytes(&image, HashAlgorithm::Sha256).unwrap();
+        let measured_digest = hex::decode(&measured_digest_hex).unwrap();
+
+        let mut log = legacy_header_event(0, EV_NO_ACTION, b"spec-id-event");
+        log.extend(pcr_event2(
+            4,
+            EV_EFI_BOOT_SERVICES_APPLICATION,
+            &measured_digest,
+            b"boot-app-load-event",
+        ));
+        let log_path = dir.join("eventlog.bin");
+        fs::write(&log_path, &log).unwrap();
+
+        let pcr = replay_pcr4(&log_path, &dir, HashAlgorithm::Sha256).unwrap();
+        let expected = HashAlgorithm::Sha256.digest_bytes(&[vec![0u8; 32], measured_digest].concat());
+        assert_eq!(pcr, expected);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,175 @@
+//! pe_authenticode.rs — Authenticode digest computation for PE/COFF images.
+//!
+//! Implements the digest half of the Authenticode spec: walk the image in
+//! file-offset order, excluding the three regions a signature can't cover —
+//! the OptionalHeader `CheckSum` field, the Certificate Table data-directory
+//! entry, and the trailing attribute certificate (signature) blob itself —
+//! and feed everything else to a selectable digest. This is the digest a
+//! TPM's `EV_EFI_BOOT_SERVICES_APPLICATION` measurement is defined over, so
+//! `pcr4.rs` calls this to replay measured-boot events.
+//!
+//! Simplifying assumption: unlike a strict Authenticode implementation, this
+//! does not re-sort section headers by `PointerToRawData` before hashing —
+//! it hashes the file as laid out on disk, which matches virtually every
+//! real linker's output (sections already appear in file-offset order).
+
+use crate::integrity::HashAlgorithm;
+use std::fs;
+use std::path::Path;
+
+const DOS_HEADER_SIZE: usize = 64;
+const PE_SIGNATURE: &[u8; 4] = b"PE\0\0";
+const PE32_PLUS_MAGIC: u16 = 0x20b;
+const IMAGE_DIRECTORY_ENTRY_SECURITY: usize = 4;
+
+/// The byte ranges of a PE image that Authenticode excludes from hashing.
+struct PeLayout {
+    checksum_offset: usize,
+    cert_dir_entry_offset: usize,
+    cert_table_file_offset: usize,
+    cert_table_size: u32,
+}
+
+impl PeLayout {
+    fn parse(image: &[u8]) -> Result<PeLayout, String> {
+        if image.len() < DOS_HEADER_SIZE {
+            return Err("Image too short to contain a DOS header".to_string());
+        }
+        let e_lfanew = read_u32(image, 0x3C, "e_lfanew")? as usize;
+
+        if image.len() < e_lfanew + 4 || &image[e_lfanew..e_lfanew + 4] != PE_SIGNATURE {
+            return Err("Not a PE image (missing 'PE\\0\\0' signature)".to_string());
+        }
+
+        let coff_offset = e_lfanew + 4;
+        let size_of_optional_header = read_u16(image, coff_offset + 16, "SizeOfOptionalHeader")? as usize;
+        if size_of_optional_header == 0 {
+            return Err("PE image has no optional header".to_string());
+        }
+        let optional_header_offset = coff_offset + 20;
+
+        let magic = read_u16(image, optional_header_offset, "Magic")?;
+        let is_pe32_plus = magic == PE32_PLUS_MAGIC;
+
+        // CheckSum sits at the same offset in both PE32 and PE32+: dropping
+        // BaseOfData (PE32 only) is exactly offset by ImageBase widening
+        // from 4 to 8 bytes in PE32+.
+        let checksum_offset = optional_header_offset + 64;
+
+        let data_directory_offset = optional_header_offset + if is_pe32_plus { 112 } else { 96 };
+        let cert_dir_entry_offset = data_directory_offset + IMAGE_DIRECTORY_ENTRY_SECURITY * 8;
+
+        if image.len() < cert_dir_entry_offset + 8 {
+            return Err("Truncated optional header / data directories".to_string());
+        }
+
+        let cert_table_file_offset = read_u32(image, cert_dir_entry_offset, "CertTable.VirtualAddress")? as usize;
+        let cert_table_size = read_u32(image, cert_dir_entry_offset + 4, "CertTable.Size")?;
+
+        Ok(PeLayout {
+            checksum_offset,
+            cert_dir_entry_offset,
+            cert_table_file_offset,
+            cert_table_size,
+        })
+    }
+}
+
+fn read_u32(data: &[u8], offset: usize, field: &str) -> Result<u32, String> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+        .ok_or_else(|| format!("Truncated PE image: missing {} at offset {}", field, offset))
+}
+
+fn read_u16(data: &[u8], offset: usize, field: &str) -> Result<u16, String> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes(b.try_into().unwrap()))
+        .ok_or_else(|| format!("Truncated PE image: missing {} at offset {}", field, offset))
+}
+
+/// Compute the Authenticode digest of a PE file on disk.
+pub fn authenticode_digest_file(path: &Path, algo: HashAlgorithm) -> Result<String, String> {
+    let bytes = fs::read(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    authenticode_digest_bytes(&bytes, algo)
+}
+
+/// Compute the Authenticode digest of PE image bytes already in memory.
+pub fn authenticode_digest_bytes(image: &[u8], algo: HashAlgorithm) -> Result<String, String> {
+    let layout = PeLayout::parse(image)?;
+
+    // No certificate table: hash through to the end of the file, which
+    // folds in any trailing data after the last section.
+    let end = if layout.cert_table_size > 0 {
+        layout.cert_table_file_offset
+    } else {
+        image.len()
+    };
+
+    if end > image.len()
+        || layout.checksum_offset + 4 > layout.cert_dir_entry_offset
+        || layout.cert_dir_entry_offset + 8 > end
+    {
+        return Err("Malformed PE layout: excluded fields fall outside the hashed range".to_string());
+    }
+
+    let mut buf = Vec::with_capacity(end);
+    buf.extend_from_slice(&image[..layout.checksum_offset]);
+    buf.extend_from_slice(&image[layout.checksum_offset + 4..layout.cert_dir_entry_offset]);
+    buf.extend_from_slice(&image[layout.cert_dir_entry_offset + 8..end]);
+
+    Ok(algo.digest_bytes(&buf))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn minimal_pe32(checksum: u32, cert_va: u32, cert_size: u32, trailer: &[u8]) -> Vec<u8> {
+        let optional_header_offset = 0x40 + 4 + 20;
+        let data_directory_offset = optional_header_offset + 96;
+        let cert_entry_offset = data_directory_offset + IMAGE_DIRECTORY_ENTRY_SECURITY * 8;
+        let mut image = vec![0u8; cert_entry_offset + 8];
+
+        image[0x3C..0x40].copy_from_slice(&(0x40u32).to_le_bytes());
+        image[0x40..0x44].copy_from_slice(PE_SIGNATURE);
+        // SizeOfOptionalHeader must be non-zero.
+        image[0x40 + 4 + 16..0x40 + 4 + 18].copy_from_slice(&96u16.to_le_bytes());
+        // Magic = PE32
+        image[optional_header_offset..optional_header_offset + 2].copy_from_slice(&0x10bu16.to_le_bytes());
+        image[optional_header_offset + 64..optional_header_offset + 68]
+            .copy_from_slice(&checksum.to_le_bytes());
+        image[cert_entry_offset..cert_entry_offset + 4].copy_from_slice(&cert_va.to_le_bytes());
+        image[cert_entry_offset + 4..cert_entry_offset + 8].copy_from_slice(&cert_size.to_le_bytes());
+        image.extend_from_slice(trailer);
+        image
+    }
+
+    #[test]
+    fn test_digest_ignores_checksum_field() {
+        let a = minimal_pe32(0x1111_1111, 0, 0, b"body");
+        let b = minimal_pe32(0x2222_2222, 0, 0, b"body");
+        assert_eq!(
+            authenticode_digest_bytes(&a, HashAlgorithm::Sha256).unwrap(),
+            authenticode_digest_bytes(&b, HashAlgorithm::Sha256).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_digest_excludes_trailing_signature() {
+        let signed = minimal_pe32(0, 0, 0, b"body");
+        let cert_offset = signed.len() as u32;
+
+        let mut with_sig = minimal_pe32(0, cert_offset, 21, b"body");
+        with_sig.extend_from_slice(b"fake-signature-bytes");
+
+        assert_eq!(
+            authenticode_digest_bytes(&signed, HashAlgorithm::Sha256).unwrap(),
+            authenticode_digest_bytes(&with_sig, HashAlgorithm::Sha256).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_rejects_non_pe_image() {
+        assert!(authenticode_digest_bytes(&[0u8; 128], HashAlgorithm::Sha256).is_err());
+    }
+}